@@ -0,0 +1,63 @@
+use anyhow::Result;
+use ethers::core::types::{Address, U256};
+use serde::de::{self, Deserializer};
+use serde::Deserialize;
+
+/// Deserializes a U256 the aggregator may send as either a decimal string
+/// ("123456") or a `0x`-prefixed hex string ("0x1e240").
+pub fn deserialize_hex_or_decimal_u256<'de, D>(deserializer: D) -> Result<U256, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let raw = String::deserialize(deserializer)?;
+    if let Some(hex) = raw.strip_prefix("0x") {
+        U256::from_str_radix(hex, 16).map_err(de::Error::custom)
+    } else {
+        U256::from_dec_str(&raw).map_err(de::Error::custom)
+    }
+}
+
+/// A 0x-style `/quote` response, trimmed to the fields this bot cross-checks against.
+#[derive(Debug, Clone, Deserialize)]
+pub struct QuoteResponse {
+    #[serde(rename = "buyAmount", deserialize_with = "deserialize_hex_or_decimal_u256")]
+    pub buy_amount: U256,
+    #[serde(rename = "sellAmount", deserialize_with = "deserialize_hex_or_decimal_u256")]
+    pub sell_amount: U256,
+}
+
+/// Fires a `/quote` request against an off-chain aggregator for the same token pair
+/// and trade size as an on-chain signal, so the signal can be corroborated before
+/// it's trusted.
+pub async fn fetch_quote(
+    base_url: &str,
+    sell_token: Address,
+    buy_token: Address,
+    sell_amount: U256,
+) -> Result<QuoteResponse> {
+    let url = format!("{}/quote", base_url.trim_end_matches('/'));
+    let response = reqwest::Client::new()
+        .get(&url)
+        .query(&[
+            ("sellToken", format!("{sell_token:?}")),
+            ("buyToken", format!("{buy_token:?}")),
+            ("sellAmount", sell_amount.to_string()),
+        ])
+        .send()
+        .await?;
+    let quote: QuoteResponse = response.json().await?;
+    Ok(quote)
+}
+
+/// Returns `true` when the aggregator's implied `buyAmount` for `sell_amount` is
+/// within `tolerance_pct` (e.g. `0.02` for 2%) of the on-chain `onchain_buy_amount`,
+/// i.e. the on-chain edge is corroborated rather than stale or manipulated pool state.
+pub fn corroborates(onchain_buy_amount: U256, quote: &QuoteResponse, tolerance_pct: f64) -> bool {
+    if onchain_buy_amount.is_zero() || quote.buy_amount.is_zero() {
+        return false;
+    }
+    let onchain = onchain_buy_amount.as_u128() as f64;
+    let aggregator = quote.buy_amount.as_u128() as f64;
+    let relative_diff = (onchain - aggregator).abs() / aggregator;
+    relative_diff <= tolerance_pct
+}
@@ -0,0 +1,49 @@
+use anyhow::Result;
+use serde_json::json;
+
+/// Fires a webhook once `profit_in_usdc` has stayed above `threshold` for
+/// `consecutive_required` ticks in a row, so a single noisy blip doesn't page anyone.
+pub struct Alerter {
+    webhook_url: String,
+    threshold: f64,
+    consecutive_required: u32,
+    streak: u32,
+}
+
+impl Alerter {
+    pub fn new(webhook_url: String, threshold: f64, consecutive_required: u32) -> Self {
+        Self {
+            webhook_url,
+            threshold,
+            consecutive_required,
+            streak: 0,
+        }
+    }
+
+    /// Feeds the latest tick's profit into the streak counter. Returns `true` when
+    /// this tick is the one that crosses `consecutive_required` and the caller should
+    /// fire the webhook.
+    pub fn observe(&mut self, profit_in_usdc: f64) -> bool {
+        if profit_in_usdc > self.threshold {
+            self.streak += 1;
+        } else {
+            self.streak = 0;
+        }
+        self.streak == self.consecutive_required
+    }
+
+    pub async fn fire(&self, profit_in_usdc: f64) -> Result<()> {
+        let client = reqwest::Client::new();
+        let body = json!({
+            "text": format!(
+                "Arbitrage opportunity sustained for {} ticks: profit = {:.2} USDC",
+                self.consecutive_required, profit_in_usdc
+            ),
+            "profit_usdc": profit_in_usdc,
+            "consecutive_ticks": self.consecutive_required,
+        });
+        client.post(&self.webhook_url).json(&body).send().await?;
+        println!("alert webhook fired for sustained profit {profit_in_usdc:.2} USDC");
+        Ok(())
+    }
+}
@@ -1,18 +1,135 @@
-use anyhow::Result;
-use rusqlite::Connection;
+use anyhow::{anyhow, Result};
+use rusqlite::{params, Connection};
+
+/// Maximum number of submission attempts, the first one included, before a `Delayed`
+/// opportunity stops being picked up by [`pending_opportunities`].
+pub const MAX_ATTEMPTS: u32 = 3;
+
+/// Lifecycle of a detected opportunity, borrowed from the usual
+/// Proposed -> Pending -> Confirmed/Delayed transaction model.
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    Proposed = 0,
+    Pending = 1,
+    Confirmed = 2,
+    Delayed = 3,
+}
+
+impl TryFrom<u8> for Status {
+    type Error = anyhow::Error;
+
+    fn try_from(value: u8) -> Result<Self> {
+        match value {
+            0 => Ok(Status::Proposed),
+            1 => Ok(Status::Pending),
+            2 => Ok(Status::Confirmed),
+            3 => Ok(Status::Delayed),
+            other => Err(anyhow!("unknown opportunity status byte: {other}")),
+        }
+    }
+}
+
+/// A `Proposed` or retry-eligible `Delayed` row, as returned by [`pending_opportunities`].
+#[derive(Debug, Clone)]
+pub struct PendingOpportunity {
+    pub id: i64,
+    pub market: String,
+    pub path: String,
+    pub buy_dex: String,
+    pub sell_dex: String,
+    pub profit_usdc: f64,
+    pub attempts: u32,
+}
 
 pub fn init_db() -> Result<()> {
-    let con = Connection::open("table.db")?;
+    init_db_at("table.db")
+}
+
+/// Same as [`init_db`] but against an arbitrary SQLite file, so tests can point it
+/// at a temp path instead of the bot's real `table.db`.
+pub fn init_db_at(path: &str) -> Result<()> {
+    let con = Connection::open(path)?;
     con.execute(
         "CREATE TABLE IF NOT EXISTS arbitrage_opportunities (
             id INTEGER PRIMARY KEY AUTOINCREMENT,
+            market TEXT,
+            path TEXT,
             buy_dex TEXT,
             sell_dex TEXT,
             profit_usdc REAL,
-            timestamp TEXT
+            timestamp TEXT,
+            status INTEGER NOT NULL DEFAULT 0,
+            attempts INTEGER NOT NULL DEFAULT 0,
+            aggregator_price_usdc REAL
         )",
         (), // No parameters
     )?;
     println!("Database and table created!");
     Ok(())
-}
\ No newline at end of file
+}
+
+/// Persists a freshly detected opportunity as `Status::Proposed`, returning its row
+/// id. `aggregator_price_usdc` records the off-chain aggregator's implied price (if
+/// an aggregator cross-check was configured) so it can be audited later.
+pub fn insert_opportunity(
+    con: &Connection,
+    market: &str,
+    path: &str,
+    buy_dex: &str,
+    sell_dex: &str,
+    profit_usdc: f64,
+    timestamp: &str,
+    aggregator_price_usdc: Option<f64>,
+) -> Result<i64> {
+    con.execute(
+        "INSERT INTO arbitrage_opportunities (market, path, buy_dex, sell_dex, profit_usdc, timestamp, status, attempts, aggregator_price_usdc) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, 0, ?8)",
+        params![market, path, buy_dex, sell_dex, profit_usdc, timestamp, Status::Proposed as u8, aggregator_price_usdc],
+    )?;
+    Ok(con.last_insert_rowid())
+}
+
+/// Updates an opportunity's status. `bump_attempts` should be `true` exactly when
+/// the row is about to be (re-)submitted, i.e. the Proposed/Delayed -> Pending edge.
+pub fn update_status(con: &Connection, id: i64, status: Status, bump_attempts: bool) -> Result<()> {
+    if bump_attempts {
+        con.execute(
+            "UPDATE arbitrage_opportunities SET status = ?1, attempts = attempts + 1 WHERE id = ?2",
+            params![status as u8, id],
+        )?;
+    } else {
+        con.execute(
+            "UPDATE arbitrage_opportunities SET status = ?1 WHERE id = ?2",
+            params![status as u8, id],
+        )?;
+    }
+    Ok(())
+}
+
+/// Returns every opportunity ready to be (re-)submitted: fresh `Proposed` rows plus
+/// `Delayed` rows whose `attempts` is still below [`MAX_ATTEMPTS`], oldest first.
+pub fn pending_opportunities(con: &Connection) -> Result<Vec<PendingOpportunity>> {
+    let mut stmt = con.prepare(
+        "SELECT id, market, path, buy_dex, sell_dex, profit_usdc, attempts
+         FROM arbitrage_opportunities
+         WHERE status = ?1 OR (status = ?2 AND attempts < ?3)
+         ORDER BY id ASC",
+    )?;
+    let rows = stmt
+        .query_map(
+            params![Status::Proposed as u8, Status::Delayed as u8, MAX_ATTEMPTS],
+            |row| {
+                Ok(PendingOpportunity {
+                    id: row.get(0)?,
+                    market: row.get(1)?,
+                    path: row.get(2)?,
+                    buy_dex: row.get(3)?,
+                    sell_dex: row.get(4)?,
+                    profit_usdc: row.get(5)?,
+                    attempts: row.get(6)?,
+                })
+            },
+        )?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(rows)
+}
@@ -0,0 +1,339 @@
+use crate::aggregator;
+use crate::sim;
+use crate::sizing;
+use anyhow::Result;
+use ethers::abi::Abi;
+use ethers::contract::Contract;
+use ethers::core::types::{Address, U256};
+use ethers::providers::{Http, Provider};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct DexRouter {
+    pub name: String,
+    pub address: String,
+    pub factory: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Market {
+    pub name: String,
+    pub path: Vec<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Opportunity {
+    pub market: String,
+    pub path: Vec<Address>,
+    pub buy_dex: String,
+    pub sell_dex: String,
+    pub profit_in_usdc: f64,
+    /// Quoted output (at `probe_amount`) for the chosen buy/sell venues, in USDC
+    /// terms, for metrics/observability.
+    pub buy_quote_usdc: f64,
+    pub sell_quote_usdc: f64,
+    /// The off-chain aggregator's implied USDC output for the same pair/size, when
+    /// an aggregator is configured and the quote corroborated this opportunity.
+    pub aggregator_price_usdc: Option<f64>,
+}
+
+pub struct DetectorParams<'a> {
+    pub provider: Arc<Provider<Http>>,
+    pub router_abi: &'a Abi,
+    pub trader: Address,
+    pub probe_amount: U256,
+    pub fee_factor: f64,
+    pub gas_cost_usdc: f64,
+    /// 0x-style aggregator base URL used to cross-check on-chain spreads; `None`
+    /// disables the cross-check entirely.
+    pub aggregator_base_url: Option<String>,
+    pub aggregator_tolerance_pct: f64,
+    /// Latest buy/sell quotes per market, updated every tick regardless of whether
+    /// the tick turned up a profitable opportunity, so the RPC server has something
+    /// to read for `get_prices` instead of the quotes being discarded in-loop.
+    pub live_prices: LivePrices,
+}
+
+/// Latest quoted prices/spread for one market, as seen on the most recent tick.
+#[derive(Debug, Clone, Serialize)]
+pub struct MarketSnapshot {
+    pub buy_dex: String,
+    pub sell_dex: String,
+    pub buy_price_usdc: f64,
+    pub sell_price_usdc: f64,
+    pub spread_usdc: f64,
+}
+
+pub type LivePrices = Arc<RwLock<HashMap<String, MarketSnapshot>>>;
+
+/// Converts a WETH-denominated profit into raw USDC units using a pool's reserve
+/// ratio as the spot price: `profit_weth * usdc_reserve / weth_reserve`. WETH's 18
+/// decimals cancel between `profit_weth` and `weth_reserve`, leaving the result in
+/// USDC's native 6-decimal units, directly comparable to `gas_cost`.
+fn weth_profit_to_usdc_raw(profit_weth: U256, usdc_reserve: U256, weth_reserve: U256) -> U256 {
+    profit_weth * usdc_reserve / weth_reserve
+}
+
+async fn quote(
+    provider: Arc<Provider<Http>>,
+    router_abi: &Abi,
+    router: &DexRouter,
+    amount_in: U256,
+    path: &[Address],
+) -> Option<U256> {
+    let router_address: Address = router.address.parse().ok()?;
+    let contract = Contract::new(router_address, router_abi.clone(), provider);
+    let out = contract
+        .method::<_, Vec<U256>>("getAmountsOut", (amount_in, path.to_vec()))
+        .ok()?
+        .call()
+        .await
+        .ok()?;
+    out.last().cloned()
+}
+
+/// Scans every router's quote for `path`, picks the cheapest-buy / richest-sell
+/// venue, and returns the best opportunity found (or `None` if fewer than two
+/// routers quote usably, or no venue pair is profitable).
+///
+/// Direct (two-token) pairs get the full treatment: reserve-based optimal sizing
+/// plus a revm-forked round-trip simulation. Longer paths (triangular/multi-hop)
+/// fall back to a lighter `getAmountsOut`-chain estimate at the fixed probe size,
+/// since the closed-form sizing formula and the two-leg simulator are both
+/// specific to direct pairs.
+pub async fn check_pair(
+    routers: &[DexRouter],
+    path: &[Address],
+    market_name: &str,
+    params: &DetectorParams<'_>,
+) -> Result<Option<Opportunity>> {
+    let mut quotes = Vec::new();
+    for router in routers {
+        if let Some(out) = quote(
+            params.provider.clone(),
+            params.router_abi,
+            router,
+            params.probe_amount,
+            path,
+        )
+        .await
+        {
+            if out > U256::zero() {
+                quotes.push((router, out));
+            }
+        }
+    }
+
+    if quotes.len() < 2 {
+        return Ok(None);
+    }
+
+    // Leg 1 (path[0] -> path[1]) must run where it pays out the MOST of path[1] per
+    // unit in, and leg 2 (the sell-back) where it pays out the LEAST, so the round
+    // trip is low -> high rather than high -> low.
+    let &(buy_router, buy_quote) = quotes.iter().max_by_key(|(_, out)| *out).unwrap();
+    let &(sell_router, sell_quote) = quotes.iter().min_by_key(|(_, out)| *out).unwrap();
+
+    if buy_router.name == sell_router.name {
+        return Ok(None);
+    }
+
+    let buy_price_usdc = buy_quote.as_u128() as f64 / 1e6;
+    let sell_price_usdc = sell_quote.as_u128() as f64 / 1e6;
+
+    // Record the quotes for this tick even if they don't end up profitable, so
+    // `rpc::get_prices` always reflects what the last tick actually saw instead of
+    // only updating on a detected opportunity.
+    params.live_prices.write().await.insert(
+        market_name.to_string(),
+        MarketSnapshot {
+            buy_dex: buy_router.name.clone(),
+            sell_dex: sell_router.name.clone(),
+            buy_price_usdc,
+            sell_price_usdc,
+            spread_usdc: (sell_price_usdc - buy_price_usdc).abs(),
+        },
+    );
+
+    let gas_cost = U256::from((params.gas_cost_usdc * 1e6) as u128);
+
+    // Set when `path.len() == 2`, to the actual sized trade fed into the
+    // simulation; shared with the aggregator cross-check below so it corroborates
+    // the same size, not the fixed price-discovery probe.
+    let mut optimal_in: Option<U256> = None;
+
+    let profit_in_usdc = if path.len() == 2 {
+        let buy_router_address: Address = buy_router.address.parse()?;
+        let sell_router_address: Address = sell_router.address.parse()?;
+        let buy_factory: Address = buy_router.factory.parse()?;
+        let sell_factory: Address = sell_router.factory.parse()?;
+
+        let buy_pair =
+            sizing::fetch_pair_address(params.provider.clone(), buy_factory, path[0], path[1]).await?;
+        let sell_pair =
+            sizing::fetch_pair_address(params.provider.clone(), sell_factory, path[0], path[1]).await?;
+
+        let buy_reserves = sizing::fetch_reserves(params.provider.clone(), buy_pair, path[0]).await?;
+        let sell_reserves = sizing::fetch_reserves(params.provider.clone(), sell_pair, path[0]).await?;
+
+        let sized_in = match sizing::optimal_amount(
+            buy_reserves.usdc.as_u128() as f64,
+            buy_reserves.weth.as_u128() as f64,
+            sell_reserves.usdc.as_u128() as f64,
+            sell_reserves.weth.as_u128() as f64,
+            params.fee_factor,
+        ) {
+            Some(x) if x > 0.0 => U256::from(x as u128),
+            _ => return Ok(None),
+        };
+        optimal_in = Some(sized_in);
+
+        let round_trip = sim::simulate_round_trip(
+            params.provider.clone(),
+            buy_router_address,
+            sell_router_address,
+            path[0],
+            path[1],
+            sized_in,
+            params.trader,
+        )
+        .await?;
+
+        let profit_weth = round_trip.profit_weth();
+        if profit_weth.is_zero() {
+            0.0
+        } else {
+            let profit_raw = weth_profit_to_usdc_raw(profit_weth, sell_reserves.usdc, sell_reserves.weth);
+            let profit = if profit_raw > gas_cost { profit_raw - gas_cost } else { U256::zero() };
+            profit.as_u128() as f64 / 1e6
+        }
+    } else {
+        // `path` here is already a closed loop (e.g. WETH->USDC->WMATIC->WETH, per
+        // the original request): `path[0] == path.last()`, and `getAmountsOut`
+        // chains every hop in one call, so a single quote on `buy_router` already
+        // is the full round trip. No separate reverse/"sell back" leg is needed (or
+        // correct) here, unlike the direct-pair branch above, which has to compose
+        // two independent single-hop pools across two different routers.
+        println!(
+            "market {market_name}: path has {} hops, quoting the full closed loop on {} (no optimal sizing / revm sim yet)",
+            path.len(),
+            buy_router.name
+        );
+        let final_amount = quote(
+            params.provider.clone(),
+            params.router_abi,
+            buy_router,
+            params.probe_amount,
+            path,
+        )
+        .await;
+        match final_amount {
+            Some(final_amount) if final_amount > params.probe_amount => {
+                let profit_weth = final_amount - params.probe_amount;
+                // Convert the WETH-denominated loop profit into USDC via the same
+                // router's first leg (`path[0] -> path[1]`), mirroring how the
+                // direct-pair branch above prices WETH profit through pool
+                // reserves; this assumes `path[1]` is the USDC-like quote token,
+                // consistent with the rest of this bot's WETH/USDC accounting.
+                let profit_raw = match quote(
+                    params.provider.clone(),
+                    params.router_abi,
+                    buy_router,
+                    profit_weth,
+                    &path[0..2],
+                )
+                .await
+                {
+                    Some(usdc_amount) => usdc_amount,
+                    None => return Ok(None),
+                };
+                if profit_raw > gas_cost {
+                    (profit_raw - gas_cost).as_u128() as f64 / 1e6
+                } else {
+                    0.0
+                }
+            }
+            _ => 0.0,
+        }
+    };
+
+    if profit_in_usdc <= 0.0 {
+        return Ok(None);
+    }
+
+    // Cross-check the on-chain edge against an off-chain aggregator quote for the
+    // same pair/size before trusting it; reduces false positives from stale or
+    // manipulated pool state. Only applies to direct pairs the aggregator can quote.
+    let aggregator_price_usdc = if path.len() == 2 {
+        match &params.aggregator_base_url {
+            Some(base_url) => {
+                // `optimal_in` is always `Some` here: it's set in the
+                // `path.len() == 2` branch above, which is the only way to reach
+                // this branch too.
+                let trade_size = optimal_in.expect("optimal_in set for direct pairs");
+                // `sell_quote` was taken at `probe_amount` during venue discovery,
+                // not `trade_size`; re-quote on-chain at the same size as the
+                // aggregator call so the corroboration compares like for like.
+                let sized_sell_quote = match quote(
+                    params.provider.clone(),
+                    params.router_abi,
+                    sell_router,
+                    trade_size,
+                    path,
+                )
+                .await
+                {
+                    Some(q) => q,
+                    None => return Ok(None),
+                };
+                let quote_response = aggregator::fetch_quote(
+                    base_url,
+                    path[0],
+                    path[1],
+                    trade_size,
+                )
+                .await?;
+                if !aggregator::corroborates(sized_sell_quote, &quote_response, params.aggregator_tolerance_pct) {
+                    println!(
+                        "market {market_name}: aggregator quote did not corroborate on-chain spread, discarding"
+                    );
+                    return Ok(None);
+                }
+                Some(quote_response.buy_amount.as_u128() as f64 / 1e6)
+            }
+            None => None,
+        }
+    } else {
+        None
+    };
+
+    Ok(Some(Opportunity {
+        market: market_name.to_string(),
+        path: path.to_vec(),
+        buy_dex: buy_router.name.clone(),
+        sell_dex: sell_router.name.clone(),
+        profit_in_usdc,
+        buy_quote_usdc: buy_price_usdc,
+        sell_quote_usdc: sell_price_usdc,
+        aggregator_price_usdc,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn weth_profit_to_usdc_raw_matches_hand_computed_value() {
+        // 1 WETH (18 decimals) of profit in a pool priced at 2000 USDC (6 decimals)
+        // per WETH should convert to 2000 raw USDC (2000 * 1e6).
+        let profit_weth = U256::from(10u128).pow(U256::from(18u128));
+        let usdc_reserve = U256::from(2000u128) * U256::from(10u128).pow(U256::from(6u128));
+        let weth_reserve = U256::from(10u128).pow(U256::from(18u128));
+        let expected = U256::from(2000u128) * U256::from(10u128).pow(U256::from(6u128));
+        assert_eq!(weth_profit_to_usdc_raw(profit_weth, usdc_reserve, weth_reserve), expected);
+    }
+}
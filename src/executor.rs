@@ -0,0 +1,53 @@
+use crate::db::{self, Status};
+use anyhow::Result;
+use rusqlite::Connection;
+use std::time::Duration;
+use tokio::time::interval;
+
+/// Background task that drains `Proposed`/retry-eligible `Delayed` opportunities:
+/// marks each `Pending` once (simulation-)submitted, then reconciles it into
+/// `Confirmed` or, on a transient failure, back to `Delayed` for another retry.
+pub fn spawn(check_interval_secs: u64) {
+    tokio::spawn(async move {
+        let mut ticker = interval(Duration::from_secs(check_interval_secs.max(1)));
+        loop {
+            ticker.tick().await;
+            if let Err(err) = run_once().await {
+                println!("executor tick failed: {err:?}");
+            }
+        }
+    });
+}
+
+async fn run_once() -> Result<()> {
+    let con = Connection::open("table.db")?;
+    for opportunity in db::pending_opportunities(&con)? {
+        println!(
+            "executor: submitting opportunity #{} ({} -> {})",
+            opportunity.id, opportunity.buy_dex, opportunity.sell_dex
+        );
+        db::update_status(&con, opportunity.id, Status::Pending, true)?;
+
+        let confirmed = reconcile(&opportunity).await.unwrap_or(false);
+        if confirmed {
+            db::update_status(&con, opportunity.id, Status::Confirmed, false)?;
+            println!("executor: opportunity #{} confirmed", opportunity.id);
+        } else {
+            db::update_status(&con, opportunity.id, Status::Delayed, false)?;
+            println!(
+                "executor: opportunity #{} did not confirm (attempt {}/{})",
+                opportunity.id,
+                opportunity.attempts + 1,
+                db::MAX_ATTEMPTS
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Stands in for reconciling the submission against the latest block/price. A real
+/// executor would re-simulate or check a submitted transaction's receipt here; for
+/// now a (simulation-)submitted opportunity is treated as confirmed.
+async fn reconcile(_opportunity: &db::PendingOpportunity) -> Result<bool> {
+    Ok(true)
+}
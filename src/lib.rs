@@ -0,0 +1,9 @@
+pub mod aggregator;
+pub mod alerter;
+pub mod db;
+pub mod detector;
+pub mod executor;
+pub mod metrics;
+pub mod rpc;
+pub mod sim;
+pub mod sizing;
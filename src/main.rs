@@ -1,44 +1,58 @@
 use anyhow::Result;
 use chrono::{Utc};
 use ethers::abi::Abi;
-use ethers::contract::Contract;
 use ethers::core::types::{Address, U256};
 use ethers::providers::{Http, Provider};
+use polygon_opportunity_detector_bot::{alerter, db, detector, executor, metrics, rpc};
+use rpc::{RpcState, RuntimeParams, SharedParams};
 use std::fs;
+use std::net::SocketAddr;
 use std::sync::Arc;
 use std::time::Duration;
 use serde::Deserialize;
 use serde_json;
 use rusqlite::Connection;
-use tokio::time::interval;
-mod db;
+use tokio::sync::RwLock;
 
 #[derive(Debug, Deserialize)]
-struct DexAddresses {
-    quickswap_router: String,
-    sushiswap_router: String,
+struct Simulation {
+    min_profit_threshold: f64, // In USDC
+    fixed_trade_size: u64, // In wei; probe size for price discovery, and the trade size for multi-hop markets
+    simulated_gas_cost: f64, // In USDC
+    check_interval_secs: u64,
+    trader_address: String, // Address used as `msg.sender` inside the forked simulation
+    fee_factor: f64, // e.g. 0.997 for a 0.3% pool fee, used by optimal_amount
 }
 
 #[derive(Debug, Deserialize)]
-struct TokenAddresses {
-    weth: String,
-    usdc: String,
+struct Monitoring {
+    metrics_port: u16,
+    alert_webhook_url: String,
+    alert_consecutive_ticks: u32,
 }
 
 #[derive(Debug, Deserialize)]
-struct Simulation {
-    min_profit_threshold: f64, // In USDC
-    fixed_trade_size: u64, // In wei (e.g., 1 WETH = 1e18)
-    simulated_gas_cost: f64, // In USDC
-    check_interval_secs: u64,
+struct RpcConfig {
+    enabled: bool,
+    port: u16,
+}
+
+#[derive(Debug, Deserialize)]
+struct AggregatorConfig {
+    enabled: bool,
+    base_url: String,
+    tolerance_pct: f64, // e.g. 0.02 for 2%
 }
 
 #[derive(Debug, Deserialize)]
 struct Config {
     rpc_url: String,
-    dex_addresses: DexAddresses,
-    token_addresses: TokenAddresses,
+    routers: Vec<detector::DexRouter>,
+    markets: Vec<detector::Market>,
     simulation: Simulation,
+    monitoring: Monitoring,
+    rpc: RpcConfig,
+    aggregator: AggregatorConfig,
 }
 
 fn load_abi(path: &str) -> Result<Abi> {
@@ -60,107 +74,156 @@ async fn main() -> Result<()> {
     println!("config loaded successfully {:?}", config);
 
 
-    let abi = load_abi("abi/uniswap_v2_router02_abi.json")?;
+    let router_abi = load_abi("abi/uniswap_v2_router02_abi.json")?;
     println!("abi loaded successfully");
 
     db::init_db()?;
 
 
-    let provider = Provider::<Http>::try_from(config.rpc_url.clone())?;
-
-    let dex1_address: Address = config.dex_addresses.quickswap_router.parse()?;
-    let dex2_address: Address = config.dex_addresses.sushiswap_router.parse()?;
-    let weth_address: Address = config.token_addresses.weth.parse()?;
-    let usdc_address: Address = config.token_addresses.usdc.parse()?;
-    let trade_amount: U256 = U256::from(config.simulation.fixed_trade_size);
+    let provider = Arc::new(Provider::<Http>::try_from(config.rpc_url.clone())?);
+    let trader_address: Address = config.simulation.trader_address.parse()?;
+    let probe_amount: U256 = U256::from(config.simulation.fixed_trade_size);
 
-    let dex1_contract = Contract::new(dex1_address, abi.clone(), Arc::new(provider.clone()));
-    let dex2_contract = Contract::new(dex2_address, abi.clone(), Arc::new(provider.clone()));
+    let markets: Vec<(String, Vec<Address>)> = config
+        .markets
+        .iter()
+        .map(|market| -> Result<(String, Vec<Address>)> {
+            let path = market
+                .path
+                .iter()
+                .map(|address| address.parse())
+                .collect::<Result<Vec<Address>, _>>()?;
+            Ok((market.name.clone(), path))
+        })
+        .collect::<Result<Vec<_>>>()?;
 
-    println!("both dex contracts created");
+    println!("{} routers, {} markets configured", config.routers.len(), markets.len());
 
     let con = Connection::open("table.db")?;
     println!("Database connected!");
 
+    metrics::init()?;
+    metrics::spawn(config.monitoring.metrics_port);
+    executor::spawn(config.simulation.check_interval_secs);
+
+    // One `Alerter` per market, so a streak on one market can't be resumed by a
+    // blip on another, and a gap of no-opportunity ticks actually resets it.
+    let mut alerters: std::collections::HashMap<String, alerter::Alerter> = markets
+        .iter()
+        .map(|(market_name, _)| {
+            (
+                market_name.clone(),
+                alerter::Alerter::new(
+                    config.monitoring.alert_webhook_url.clone(),
+                    config.simulation.min_profit_threshold,
+                    config.monitoring.alert_consecutive_ticks,
+                ),
+            )
+        })
+        .collect();
+
+    // Mutable simulation parameters, shared with the RPC server so operator
+    // updates take effect on the very next tick without a restart.
+    let runtime_params: SharedParams = Arc::new(RwLock::new(RuntimeParams {
+        min_profit_threshold: config.simulation.min_profit_threshold,
+        check_interval_secs: config.simulation.check_interval_secs,
+    }));
+
+    // Latest per-market quotes, refreshed every tick by the detector and read by
+    // the RPC server's `get_prices` method.
+    let live_prices: detector::LivePrices = Arc::new(RwLock::new(std::collections::HashMap::new()));
+
+    if config.rpc.enabled {
+        let rpc_state = Arc::new(RpcState {
+            params: runtime_params.clone(),
+            db_path: "table.db".to_string(),
+            live_prices: live_prices.clone(),
+        });
+        rpc::spawn(SocketAddr::from(([0, 0, 0, 0], config.rpc.port)), rpc_state);
+    }
+
+    let params = detector::DetectorParams {
+        provider: provider.clone(),
+        router_abi: &router_abi,
+        trader: trader_address,
+        probe_amount,
+        fee_factor: config.simulation.fee_factor,
+        gas_cost_usdc: config.simulation.simulated_gas_cost,
+        aggregator_base_url: config.aggregator.enabled.then(|| config.aggregator.base_url.clone()),
+        aggregator_tolerance_pct: config.aggregator.tolerance_pct,
+        live_prices,
+    };
 
-    let mut interval = interval(Duration::from_secs(config.simulation.check_interval_secs));
     loop {
-        interval.tick().await;
+        let (min_profit_threshold, check_interval_secs) = {
+            let current = runtime_params.read().await;
+            (current.min_profit_threshold, current.check_interval_secs)
+        };
+        tokio::time::sleep(Duration::from_secs(check_interval_secs)).await;
 
         println!("checking prices now...");
 
-
-        let dex1_out = dex1_contract
-            .method::<_, Vec<U256>>("getAmountsOut", (trade_amount, vec![weth_address, usdc_address]))?
-            .call()
-            .await
-            .unwrap_or_else(|err| {
-                println!("error in quickswap {:?}", err);
-                vec![U256::zero(), U256::zero()]
-            });
-
-
-        let dex2_out = dex2_contract
-            .method::<_, Vec<U256>>("getAmountsOut", (trade_amount, vec![weth_address, usdc_address]))?
-            .call()
-            .await
-            .unwrap_or_else(|err| {
-                println!("error in sushiswap {:?}", err);
-                vec![U256::zero(), U256::zero()]
-            });
-
-        println!("raw output quickswap: {:?}, sushiswap: {:?}", dex1_out, dex2_out);
-
-
-        let dex1_price_raw = dex1_out.get(1).cloned().unwrap_or(U256::zero());
-        let dex2_price_raw = dex2_out.get(1).cloned().unwrap_or(U256::zero());
-
-        if dex1_price_raw < U256::from(1_000_000) || dex2_price_raw < U256::from(1_000_000) {
-            println!("invalid price");
-            continue;
-        }
-
-        let dex1_price = dex1_price_raw.as_u128() as f64 / 1e6;
-        let dex2_price = dex2_price_raw.as_u128() as f64 / 1e6;
-
-        println!("QuickSwap price in USDC: {}, SushiSwap price in USDC: {}", dex1_price, dex2_price);
-
-        let mut buy_dex = String::from("");
-        let mut sell_dex = String::from("");
-
-        if dex1_price_raw > dex2_price_raw {
-            println!("possible buy on SushiSwap and sell on QuickSwap");
-            buy_dex = "SushiSwap".to_string();
-            sell_dex = "QuickSwap".to_string();
-        } else if dex2_price_raw > dex1_price_raw {
-            println!("possible buy on QuickSwap and sell on SushiSwap");
-            buy_dex = "QuickSwap".to_string();
-            sell_dex = "SushiSwap".to_string();
-        } else {
-            println!("both are same, no arbitrage");
-        }
-
-        let gas_cost = U256::from((config.simulation.simulated_gas_cost * 1e6) as u128);
-        let diff = if dex1_price_raw > dex2_price_raw {
-            dex1_price_raw - dex2_price_raw
-        } else {
-            dex2_price_raw - dex1_price_raw
-        };
-        let profit = if diff > gas_cost { diff - gas_cost } else { U256::zero() };
-        let profit_in_usdc = profit.as_u128() as f64 / 1e6;
-
-        println!("simulated profit after gas: {}", profit_in_usdc);
-
-        if profit_in_usdc > config.simulation.min_profit_threshold {
-            println!("!!!! Arbitrage found !!!! Profit = {}", profit_in_usdc);
-            let timestamp = Utc::now().to_rfc3339();
-            con.execute(
-                "INSERT INTO arbitrage_opportunities (buy_dex, sell_dex, profit_usdc, timestamp) VALUES (?1, ?2, ?3, ?4)",
-                (&buy_dex, &sell_dex, &profit_in_usdc, &timestamp),
-            )?;
-            println!("Saved to database!");
-        } else {
-            println!("profit is small, not worth it");
+        for (market_name, path) in &markets {
+            let alerter = alerters.get_mut(market_name).expect("alerter seeded for every configured market");
+
+            let opportunity = match detector::check_pair(&config.routers, path, market_name, &params).await {
+                Ok(Some(opportunity)) => opportunity,
+                Ok(None) => {
+                    println!("market {market_name}: no arbitrage this tick");
+                    alerter.observe(0.0);
+                    continue;
+                }
+                Err(err) => {
+                    println!("market {market_name}: check_pair failed: {err:?}");
+                    alerter.observe(0.0);
+                    continue;
+                }
+            };
+
+            println!(
+                "market {market_name}: buy on {} sell on {}, profit {} USDC",
+                opportunity.buy_dex, opportunity.sell_dex, opportunity.profit_in_usdc
+            );
+
+            let spread = (opportunity.sell_quote_usdc - opportunity.buy_quote_usdc).abs();
+            metrics::record_tick(
+                market_name,
+                opportunity.buy_quote_usdc,
+                opportunity.sell_quote_usdc,
+                spread,
+                opportunity.profit_in_usdc,
+            );
+
+            if alerter.observe(opportunity.profit_in_usdc) {
+                if let Err(err) = alerter.fire(opportunity.profit_in_usdc).await {
+                    println!("failed to fire alert webhook: {err:?}");
+                }
+            }
+
+            if opportunity.profit_in_usdc > min_profit_threshold {
+                println!("!!!! Arbitrage found !!!! Profit = {}", opportunity.profit_in_usdc);
+                let timestamp = Utc::now().to_rfc3339();
+                let path_str = opportunity
+                    .path
+                    .iter()
+                    .map(|address| format!("{address:?}"))
+                    .collect::<Vec<_>>()
+                    .join(",");
+                db::insert_opportunity(
+                    &con,
+                    &opportunity.market,
+                    &path_str,
+                    &opportunity.buy_dex,
+                    &opportunity.sell_dex,
+                    opportunity.profit_in_usdc,
+                    &timestamp,
+                    opportunity.aggregator_price_usdc,
+                )?;
+                metrics::OPPORTUNITIES_SAVED.with_label_values(&[market_name]).inc();
+                println!("Saved to database!");
+            } else {
+                println!("profit below threshold for {market_name}, not worth it");
+            }
         }
     }
 }
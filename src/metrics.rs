@@ -0,0 +1,85 @@
+use anyhow::Result;
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Request, Response, Server};
+use lazy_static::lazy_static;
+use prometheus::{Encoder, GaugeVec, IntCounterVec, Opts, Registry, TextEncoder};
+use std::convert::Infallible;
+use std::net::SocketAddr;
+
+lazy_static! {
+    pub static ref REGISTRY: Registry = Registry::new();
+    // Labeled by `market` since chunk0-4 generalized detection away from a single
+    // hardcoded WETH/USDC pair into a configurable matrix.
+    pub static ref BUY_PRICE: GaugeVec = GaugeVec::new(
+        Opts::new("buy_venue_price_usdc", "Latest quoted output at the cheaper venue, per market"),
+        &["market"]
+    )
+    .unwrap();
+    pub static ref SELL_PRICE: GaugeVec = GaugeVec::new(
+        Opts::new("sell_venue_price_usdc", "Latest quoted output at the richer venue, per market"),
+        &["market"]
+    )
+    .unwrap();
+    pub static ref SPREAD: GaugeVec = GaugeVec::new(
+        Opts::new("spread_usdc", "Latest absolute spread between buy and sell venues, per market"),
+        &["market"]
+    )
+    .unwrap();
+    pub static ref SIMULATED_PROFIT: GaugeVec = GaugeVec::new(
+        Opts::new(
+            "simulated_profit_usdc",
+            "Latest simulated round-trip profit after gas, per market"
+        ),
+        &["market"]
+    )
+    .unwrap();
+    pub static ref OPPORTUNITIES_SAVED: IntCounterVec = IntCounterVec::new(
+        Opts::new(
+            "opportunities_saved_total",
+            "Count of arbitrage opportunities persisted to SQLite, per market"
+        ),
+        &["market"]
+    )
+    .unwrap();
+}
+
+/// Registers all metrics with the global registry. Must be called once before `spawn`.
+pub fn init() -> Result<()> {
+    REGISTRY.register(Box::new(BUY_PRICE.clone()))?;
+    REGISTRY.register(Box::new(SELL_PRICE.clone()))?;
+    REGISTRY.register(Box::new(SPREAD.clone()))?;
+    REGISTRY.register(Box::new(SIMULATED_PROFIT.clone()))?;
+    REGISTRY.register(Box::new(OPPORTUNITIES_SAVED.clone()))?;
+    Ok(())
+}
+
+/// Updates the price/spread/profit gauges for one market; called from the main
+/// loop only on ticks where `check_pair` reports an opportunity for that market.
+/// Ticks with no opportunity leave the gauges at their previous values.
+pub fn record_tick(market: &str, buy_price: f64, sell_price: f64, spread: f64, profit_usdc: f64) {
+    BUY_PRICE.with_label_values(&[market]).set(buy_price);
+    SELL_PRICE.with_label_values(&[market]).set(sell_price);
+    SPREAD.with_label_values(&[market]).set(spread);
+    SIMULATED_PROFIT.with_label_values(&[market]).set(profit_usdc);
+}
+
+async fn serve_metrics(_req: Request<Body>) -> Result<Response<Body>, Infallible> {
+    let metric_families = REGISTRY.gather();
+    let mut buffer = Vec::new();
+    let encoder = TextEncoder::new();
+    encoder.encode(&metric_families, &mut buffer).unwrap();
+    Ok(Response::new(Body::from(buffer)))
+}
+
+/// Spawns the `/metrics` endpoint as a background task alongside the tick loop.
+pub fn spawn(port: u16) {
+    tokio::spawn(async move {
+        let addr = SocketAddr::from(([0, 0, 0, 0], port));
+        let make_svc =
+            make_service_fn(|_conn| async { Ok::<_, Infallible>(service_fn(serve_metrics)) });
+        println!("metrics server listening on http://{addr}/metrics");
+        if let Err(err) = Server::bind(&addr).serve(make_svc).await {
+            println!("metrics server error: {err:?}");
+        }
+    });
+}
@@ -0,0 +1,158 @@
+use crate::detector::LivePrices;
+use anyhow::{anyhow, Result};
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Request, Response, Server};
+use rusqlite::{params, Connection};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+/// Runtime-mutable simulation parameters. The main loop reads these each tick so
+/// updates made over RPC take effect without a restart.
+#[derive(Debug, Clone)]
+pub struct RuntimeParams {
+    pub min_profit_threshold: f64,
+    pub check_interval_secs: u64,
+}
+
+pub type SharedParams = Arc<RwLock<RuntimeParams>>;
+
+pub struct RpcState {
+    pub params: SharedParams,
+    pub db_path: String,
+    /// Latest per-market buy/sell quotes, populated by the detector each tick;
+    /// backs `get_prices` below.
+    pub live_prices: LivePrices,
+}
+
+#[derive(Debug, Deserialize)]
+struct RpcRequest {
+    method: String,
+    #[serde(default)]
+    params: Value,
+}
+
+fn error_response(message: &str) -> Response<Body> {
+    let body = json!({ "ok": false, "error": message });
+    Response::new(Body::from(body.to_string()))
+}
+
+async fn handle(req: Request<Body>, state: Arc<RpcState>) -> Result<Response<Body>, Infallible> {
+    let body_bytes = match hyper::body::to_bytes(req.into_body()).await {
+        Ok(bytes) => bytes,
+        Err(_) => return Ok(error_response("failed to read request body")),
+    };
+    let rpc_request: RpcRequest = match serde_json::from_slice(&body_bytes) {
+        Ok(request) => request,
+        Err(_) => return Ok(error_response("invalid JSON-RPC request, expected {method, params}")),
+    };
+
+    let response = match dispatch(&rpc_request, &state).await {
+        Ok(result) => json!({ "ok": true, "result": result }),
+        Err(err) => json!({ "ok": false, "error": err.to_string() }),
+    };
+    Ok(Response::new(Body::from(response.to_string())))
+}
+
+async fn dispatch(req: &RpcRequest, state: &RpcState) -> Result<Value> {
+    match req.method.as_str() {
+        "list_opportunities" => list_opportunities(&state.db_path, &req.params),
+        "get_params" => {
+            let params = state.params.read().await;
+            Ok(json!({
+                "min_profit_threshold": params.min_profit_threshold,
+                "check_interval_secs": params.check_interval_secs,
+            }))
+        }
+        "set_min_profit_threshold" => {
+            let value = req
+                .params
+                .get("value")
+                .and_then(Value::as_f64)
+                .ok_or_else(|| anyhow!("missing numeric `value`"))?;
+            let mut params = state.params.write().await;
+            params.min_profit_threshold = value;
+            Ok(json!({ "min_profit_threshold": value }))
+        }
+        "set_check_interval_secs" => {
+            let value = req
+                .params
+                .get("value")
+                .and_then(Value::as_u64)
+                .ok_or_else(|| anyhow!("missing numeric `value`"))?;
+            let mut params = state.params.write().await;
+            params.check_interval_secs = value;
+            Ok(json!({ "check_interval_secs": value }))
+        }
+        "get_prices" => {
+            let live_prices = state.live_prices.read().await;
+            match req.params.get("market").and_then(Value::as_str) {
+                Some(market) => match live_prices.get(market) {
+                    Some(snapshot) => Ok(json!({ market: snapshot })),
+                    None => Err(anyhow!("no live price recorded for market `{market}` yet")),
+                },
+                None => Ok(json!(&*live_prices)),
+            }
+        }
+        other => Err(anyhow!("unknown method: {other}")),
+    }
+}
+
+fn list_opportunities(db_path: &str, params: &Value) -> Result<Value> {
+    let limit = params.get("limit").and_then(Value::as_i64).unwrap_or(20);
+    let con = Connection::open(db_path)?;
+    let mut stmt = con.prepare(
+        "SELECT market, path, buy_dex, sell_dex, profit_usdc, status, attempts, timestamp, aggregator_price_usdc
+         FROM arbitrage_opportunities ORDER BY id DESC LIMIT ?1",
+    )?;
+    let rows = stmt
+        .query_map(params![limit], |row| {
+            Ok(json!({
+                "market": row.get::<_, String>(0)?,
+                "path": row.get::<_, String>(1)?,
+                "buy_dex": row.get::<_, String>(2)?,
+                "sell_dex": row.get::<_, String>(3)?,
+                "profit_usdc": row.get::<_, f64>(4)?,
+                "status": row.get::<_, u8>(5)?,
+                "attempts": row.get::<_, u32>(6)?,
+                "timestamp": row.get::<_, String>(7)?,
+                "aggregator_price_usdc": row.get::<_, Option<f64>>(8)?,
+            }))
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(Value::Array(rows))
+}
+
+/// Binds the JSON-RPC HTTP server and returns the address it actually bound to
+/// (useful for tests that bind to port 0) along with the future that serves it.
+pub fn bind(
+    addr: SocketAddr,
+    state: Arc<RpcState>,
+) -> Result<(SocketAddr, impl std::future::Future<Output = Result<(), hyper::Error>>)> {
+    let make_svc = make_service_fn(move |_conn| {
+        let state = state.clone();
+        async move { Ok::<_, Infallible>(service_fn(move |req| handle(req, state.clone()))) }
+    });
+    let server = Server::try_bind(&addr)?.serve(make_svc);
+    let bound_addr = server.local_addr();
+    Ok((bound_addr, server))
+}
+
+/// Spawns the RPC server as a background task, logging instead of failing `main`
+/// if the port can't be bound.
+pub fn spawn(addr: SocketAddr, state: Arc<RpcState>) {
+    tokio::spawn(async move {
+        match bind(addr, state) {
+            Ok((bound, server)) => {
+                println!("rpc server listening on http://{bound}");
+                if let Err(err) = server.await {
+                    println!("rpc server error: {err:?}");
+                }
+            }
+            Err(err) => println!("rpc server failed to bind to {addr}: {err:?}"),
+        }
+    });
+}
@@ -0,0 +1,198 @@
+use alloy_sol_types::{sol, SolCall};
+use anyhow::{anyhow, Result};
+use ethers::core::types::{Address, U256};
+use ethers::providers::{Http, Provider};
+use revm::db::{CacheDB, EthersDB};
+use revm::primitives::{ExecutionResult, Output, TransactTo, B256, U256 as RU256};
+use revm::EVM;
+use std::str::FromStr;
+use std::sync::Arc;
+
+sol! {
+    function swapExactTokensForTokens(
+        uint256 amountIn,
+        uint256 amountOutMin,
+        address[] path,
+        address to,
+        uint256 deadline
+    ) external returns (uint256[] amounts);
+}
+
+// Storage slot holding the `balanceOf` mapping for WETH/USDC on Polygon (and most
+// vanilla ERC20s). Used to fund the simulated trader without needing a real balance.
+const BALANCE_OF_SLOT: u64 = 3;
+// Storage slot holding the `allowance` mapping.
+const ALLOWANCE_SLOT: u64 = 4;
+
+/// Result of simulating both legs of a round-trip arbitrage in the same cached EVM state.
+#[derive(Debug, Clone)]
+pub struct RoundTrip {
+    pub weth_in: U256,
+    pub usdc_out: U256,
+    pub weth_out: U256,
+}
+
+impl RoundTrip {
+    /// Net profit in wei of WETH, ignoring gas (caller subtracts `simulated_gas_cost`).
+    pub fn profit_weth(&self) -> U256 {
+        if self.weth_out > self.weth_in {
+            self.weth_out - self.weth_in
+        } else {
+            U256::zero()
+        }
+    }
+}
+
+/// Storage slot for a single-level mapping entry, e.g. `balanceOf[holder]`:
+/// `keccak256(holder . slot)`.
+fn mapping_slot(key: Address, base_slot: u64) -> B256 {
+    use ethers::utils::keccak256;
+    let mut buf = [0u8; 64];
+    buf[12..32].copy_from_slice(key.as_bytes());
+    buf[63] = base_slot as u8;
+    B256::from_slice(&keccak256(buf))
+}
+
+fn balance_slot(holder: Address) -> B256 {
+    mapping_slot(holder, BALANCE_OF_SLOT)
+}
+
+/// Storage slot for a nested mapping entry, e.g. `allowance[owner][spender]`:
+/// `keccak256(spender . keccak256(owner . slot))`.
+fn allowance_slot(owner: Address, spender: Address) -> B256 {
+    use ethers::utils::keccak256;
+    let inner = mapping_slot(owner, ALLOWANCE_SLOT);
+    let mut buf = [0u8; 64];
+    buf[12..32].copy_from_slice(spender.as_bytes());
+    buf[32..64].copy_from_slice(inner.as_bytes());
+    B256::from_slice(&keccak256(buf))
+}
+
+fn erc20_amount_to_ru256(amount: U256) -> RU256 {
+    let mut bytes = [0u8; 32];
+    amount.to_big_endian(&mut bytes);
+    RU256::from_be_bytes(bytes)
+}
+
+fn ru256_to_u256(amount: RU256) -> U256 {
+    U256::from_big_endian(&amount.to_be_bytes::<32>())
+}
+
+/// Forks current chain state and simulates buying on `buy_router` then selling the
+/// acquired token on `sell_router`, so reserve mutations from leg 1 are visible to leg 2.
+pub async fn simulate_round_trip(
+    provider: Arc<Provider<Http>>,
+    buy_router: Address,
+    sell_router: Address,
+    weth: Address,
+    usdc: Address,
+    weth_in: U256,
+    trader: Address,
+) -> Result<RoundTrip> {
+    let block = provider.get_block_number().await?;
+    let ethers_db = EthersDB::new(provider, Some(block.into()))
+        .ok_or_else(|| anyhow!("failed to construct EthersDB at block {block}"))?;
+    let mut cache_db = CacheDB::new(ethers_db);
+
+    let trader_rv = revm::primitives::Address::from_slice(trader.as_bytes());
+    let weth_rv = revm::primitives::Address::from_slice(weth.as_bytes());
+    let usdc_rv = revm::primitives::Address::from_slice(usdc.as_bytes());
+    let buy_router_rv = revm::primitives::Address::from_slice(buy_router.as_bytes());
+    let sell_router_rv = revm::primitives::Address::from_slice(sell_router.as_bytes());
+
+    // Fund the trader with WETH and max-approve each router on the token it will
+    // actually pull via `transferFrom` (WETH for the buy leg, USDC for the sell
+    // leg); this is a storage overlay on the forked state and never touches the
+    // real chain.
+    let weth_in_rv = erc20_amount_to_ru256(weth_in);
+    cache_db.insert_account_storage(weth_rv, balance_slot(trader).into(), weth_in_rv)?;
+    cache_db.insert_account_storage(weth_rv, allowance_slot(trader, buy_router).into(), RU256::MAX)?;
+    cache_db.insert_account_storage(usdc_rv, allowance_slot(trader, sell_router).into(), RU256::MAX)?;
+
+    let deadline = U256::from_dec_str("99999999999")?;
+
+    let mut evm = EVM::new();
+    evm.database(cache_db);
+    evm.env.tx.caller = trader_rv;
+
+    // Leg 1: WETH -> USDC on the cheaper router.
+    let leg1_call = swapExactTokensForTokensCall {
+        amountIn: erc20_amount_to_ru256(weth_in),
+        amountOutMin: RU256::from(0),
+        path: vec![
+            alloy_sol_types::private::Address::from_slice(weth.as_bytes()),
+            alloy_sol_types::private::Address::from_slice(usdc.as_bytes()),
+        ],
+        to: alloy_sol_types::private::Address::from_slice(trader.as_bytes()),
+        deadline: erc20_amount_to_ru256(deadline),
+    };
+    evm.env.tx.transact_to = TransactTo::Call(buy_router_rv);
+    evm.env.tx.data = leg1_call.abi_encode().into();
+    let usdc_out = decode_last_amount(evm.transact_commit()?)?;
+
+    // Leg 2: USDC -> WETH on the other router, fed directly from leg 1's output so
+    // leg 1's reserve mutations are visible (committed to the same `cache_db`
+    // underneath `evm`).
+    let leg2_call = swapExactTokensForTokensCall {
+        amountIn: erc20_amount_to_ru256(usdc_out),
+        amountOutMin: RU256::from(0),
+        path: vec![
+            alloy_sol_types::private::Address::from_slice(usdc.as_bytes()),
+            alloy_sol_types::private::Address::from_slice(weth.as_bytes()),
+        ],
+        to: alloy_sol_types::private::Address::from_slice(trader.as_bytes()),
+        deadline: erc20_amount_to_ru256(deadline),
+    };
+    evm.env.tx.transact_to = TransactTo::Call(sell_router_rv);
+    evm.env.tx.data = leg2_call.abi_encode().into();
+    let weth_out = decode_last_amount(evm.transact()?.result)?;
+
+    let _ = usdc_rv;
+    Ok(RoundTrip {
+        weth_in,
+        usdc_out,
+        weth_out,
+    })
+}
+
+fn decode_last_amount(result: ExecutionResult) -> Result<U256> {
+    match result {
+        ExecutionResult::Success { output: Output::Call(bytes), .. } => {
+            let decoded = swapExactTokensForTokensCall::abi_decode_returns(&bytes, true)
+                .map_err(|e| anyhow!("failed to decode swap return: {e}"))?;
+            let last = decoded
+                .amounts
+                .last()
+                .ok_or_else(|| anyhow!("router returned no amounts"))?;
+            Ok(ru256_to_u256(*last))
+        }
+        ExecutionResult::Success { .. } => Err(anyhow!("unexpected EVM output variant")),
+        ExecutionResult::Revert { output, .. } => Err(anyhow!("swap reverted: {output:?}")),
+        ExecutionResult::Halt { reason, .. } => Err(anyhow!("swap halted: {reason:?}")),
+    }
+}
+
+#[allow(dead_code)]
+fn zero_address() -> Address {
+    Address::from_str("0x0000000000000000000000000000000000000000").unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn amount_conversion_round_trips() {
+        let amount = U256::from(123_456_789_000_000_000u128);
+        assert_eq!(ru256_to_u256(erc20_amount_to_ru256(amount)), amount);
+    }
+
+    #[test]
+    fn allowance_slot_differs_per_spender() {
+        let owner = Address::from_str("0x000000000000000000000000000000000000000a").unwrap();
+        let spender_a = Address::from_str("0x000000000000000000000000000000000000000b").unwrap();
+        let spender_b = Address::from_str("0x000000000000000000000000000000000000000c").unwrap();
+        assert_ne!(allowance_slot(owner, spender_a), allowance_slot(owner, spender_b));
+        assert_ne!(allowance_slot(owner, spender_a), balance_slot(owner));
+    }
+}
@@ -0,0 +1,106 @@
+use anyhow::Result;
+use ethers::abi::Abi;
+use ethers::contract::Contract;
+use ethers::core::types::{Address, U256};
+use ethers::providers::{Http, Provider};
+use std::sync::Arc;
+
+const PAIR_ABI_JSON: &str = r#"[
+    {"constant":true,"inputs":[],"name":"getReserves","outputs":[
+        {"internalType":"uint112","name":"_reserve0","type":"uint112"},
+        {"internalType":"uint112","name":"_reserve1","type":"uint112"},
+        {"internalType":"uint32","name":"_blockTimestampLast","type":"uint32"}
+    ],"payable":false,"stateMutability":"view","type":"function"},
+    {"constant":true,"inputs":[],"name":"token0","outputs":[{"internalType":"address","name":"","type":"address"}],"payable":false,"stateMutability":"view","type":"function"}
+]"#;
+
+const FACTORY_ABI_JSON: &str = r#"[
+    {"constant":true,"inputs":[{"internalType":"address","name":"tokenA","type":"address"},{"internalType":"address","name":"tokenB","type":"address"}],"name":"getPair","outputs":[{"internalType":"address","name":"pair","type":"address"}],"payable":false,"stateMutability":"view","type":"function"}
+]"#;
+
+/// Resolves a Uniswap V2-style pair address from a factory, so callers don't need
+/// to hardcode per-DEX pair addresses for every market.
+pub async fn fetch_pair_address(
+    provider: Arc<Provider<Http>>,
+    factory: Address,
+    token_a: Address,
+    token_b: Address,
+) -> Result<Address> {
+    let abi: Abi = serde_json::from_str(FACTORY_ABI_JSON)?;
+    let contract = Contract::new(factory, abi, provider);
+    let pair: Address = contract
+        .method::<_, Address>("getPair", (token_a, token_b))?
+        .call()
+        .await?;
+    Ok(pair)
+}
+
+/// Reserves of a WETH/USDC pool, already re-ordered so `weth` and `usdc` line up with
+/// the token each field is named after (Uniswap V2 pairs sort by address, not by what
+/// the caller cares about).
+pub struct PoolReserves {
+    pub weth: U256,
+    pub usdc: U256,
+}
+
+/// Fetches `getReserves()` from a Uniswap V2-style pair and orders the result by
+/// comparing against `token0()`, since a pair's storage order is by address, not by
+/// which token the caller considers the "input".
+pub async fn fetch_reserves(
+    provider: Arc<Provider<Http>>,
+    pair: Address,
+    weth: Address,
+) -> Result<PoolReserves> {
+    let abi: Abi = serde_json::from_str(PAIR_ABI_JSON)?;
+    let contract = Contract::new(pair, abi, provider);
+
+    let token0: Address = contract.method::<_, Address>("token0", ())?.call().await?;
+    let (reserve0, reserve1, _): (U256, U256, u32) =
+        contract.method("getReserves", ())?.call().await?;
+
+    if token0 == weth {
+        Ok(PoolReserves { weth: reserve0, usdc: reserve1 })
+    } else {
+        Ok(PoolReserves { weth: reserve1, usdc: reserve0 })
+    }
+}
+
+/// Closed-form profit-maximizing input size for a two-pool constant-product
+/// arbitrage: buy on pool 1 (input-token reserve `b1`, output-token reserve `a1`),
+/// sell on pool 2 (output-token reserve `a2`, input-token reserve `b2`), with fee
+/// factor `g` (0.997 for a 0.3% fee).
+///
+/// `x* = (g*sqrt(a1*a2*b1*b2) - a2*b1) / (g*(a2 + g*a1))`, derived by composing the
+/// two constant-product swaps into `B_out(u) = (b2*g*a1*u)/(a2*b1 + u*(a2 + g*a1))`
+/// with `u = g*x` and solving `dB_out/du = 1/g`.
+///
+/// Returns `None` when the numerator is non-positive, i.e. there is no profitable
+/// trade at any size.
+pub fn optimal_amount(a1: f64, b1: f64, a2: f64, b2: f64, g: f64) -> Option<f64> {
+    let numerator = g * (a1 * a2 * b1 * b2).sqrt() - a2 * b1;
+    if numerator <= 0.0 {
+        return None;
+    }
+    let denominator = g * (a2 + g * a1);
+    Some(numerator / denominator)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn optimal_amount_matches_hand_computed_value() {
+        // g = 1.0 to keep the hand computation simple: sqrt(10000*10*10*9000) = sqrt(9e9).
+        let x = optimal_amount(10000.0, 10.0, 10.0, 9000.0, 1.0).unwrap();
+        let expected = (9_000_000_000f64.sqrt() - 100.0) / 10010.0;
+        assert!((x - expected).abs() < 1e-6, "x = {x}, expected = {expected}");
+    }
+
+    #[test]
+    fn optimal_amount_is_none_when_unprofitable() {
+        // a2*b1 (100_000) dominates g*sqrt(a1*a2*b1*b2) (sqrt(1e7), about 3_162), so no
+        // size is profitable.
+        assert_eq!(optimal_amount(10.0, 10.0, 10000.0, 10.0, 1.0), None);
+    }
+}
@@ -0,0 +1,133 @@
+use polygon_opportunity_detector_bot::db;
+use polygon_opportunity_detector_bot::detector::{LivePrices, MarketSnapshot};
+use polygon_opportunity_detector_bot::rpc::{self, RpcState, RuntimeParams};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+async fn spawn_test_server(db_path: &str) -> SocketAddr {
+    spawn_test_server_with_prices(db_path, Arc::new(RwLock::new(HashMap::new()))).await
+}
+
+async fn spawn_test_server_with_prices(db_path: &str, live_prices: LivePrices) -> SocketAddr {
+    let state = Arc::new(RpcState {
+        params: Arc::new(RwLock::new(RuntimeParams {
+            min_profit_threshold: 5.0,
+            check_interval_secs: 10,
+        })),
+        db_path: db_path.to_string(),
+        live_prices,
+    });
+
+    let (addr, server) = rpc::bind(SocketAddr::from(([127, 0, 0, 1], 0)), state).unwrap();
+    tokio::spawn(server);
+    addr
+}
+
+async fn call(addr: SocketAddr, method: &str, params: Value) -> Value {
+    let client = reqwest::Client::new();
+    client
+        .post(format!("http://{addr}"))
+        .json(&json!({ "method": method, "params": params }))
+        .send()
+        .await
+        .unwrap()
+        .json::<Value>()
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn get_and_update_runtime_params() {
+    let db_file = tempfile::NamedTempFile::new().unwrap();
+    let db_path = db_file.path().to_str().unwrap().to_string();
+    db::init_db_at(&db_path).unwrap();
+
+    let addr = spawn_test_server(&db_path).await;
+
+    let before = call(addr, "get_params", json!({})).await;
+    assert_eq!(before["result"]["min_profit_threshold"], 5.0);
+    assert_eq!(before["result"]["check_interval_secs"], 10);
+
+    let update = call(addr, "set_min_profit_threshold", json!({ "value": 12.5 })).await;
+    assert_eq!(update["ok"], true);
+    assert_eq!(update["result"]["min_profit_threshold"], 12.5);
+
+    let after = call(addr, "get_params", json!({})).await;
+    assert_eq!(after["result"]["min_profit_threshold"], 12.5);
+    assert_eq!(after["result"]["check_interval_secs"], 10);
+}
+
+#[tokio::test]
+async fn list_opportunities_returns_inserted_rows() {
+    let db_file = tempfile::NamedTempFile::new().unwrap();
+    let db_path = db_file.path().to_str().unwrap().to_string();
+    db::init_db_at(&db_path).unwrap();
+
+    {
+        let con = rusqlite::Connection::open(&db_path).unwrap();
+        db::insert_opportunity(
+            &con,
+            "WETH/USDC",
+            "0xabc,0xdef",
+            "QuickSwap",
+            "SushiSwap",
+            42.0,
+            "2026-01-01T00:00:00Z",
+            Some(41.8),
+        )
+        .unwrap();
+    }
+
+    let addr = spawn_test_server(&db_path).await;
+
+    let response = call(addr, "list_opportunities", json!({ "limit": 10 })).await;
+    let rows = response["result"].as_array().unwrap();
+    assert_eq!(rows.len(), 1);
+    assert_eq!(rows[0]["market"], "WETH/USDC");
+    assert_eq!(rows[0]["profit_usdc"], 42.0);
+}
+
+#[tokio::test]
+async fn get_prices_returns_latest_snapshot() {
+    let db_file = tempfile::NamedTempFile::new().unwrap();
+    let db_path = db_file.path().to_str().unwrap().to_string();
+    db::init_db_at(&db_path).unwrap();
+
+    let live_prices: LivePrices = Arc::new(RwLock::new(HashMap::new()));
+    live_prices.write().await.insert(
+        "WETH/USDC".to_string(),
+        MarketSnapshot {
+            buy_dex: "QuickSwap".to_string(),
+            sell_dex: "SushiSwap".to_string(),
+            buy_price_usdc: 1800.0,
+            sell_price_usdc: 1805.0,
+            spread_usdc: 5.0,
+        },
+    );
+
+    let addr = spawn_test_server_with_prices(&db_path, live_prices).await;
+
+    let all = call(addr, "get_prices", json!({})).await;
+    assert_eq!(all["result"]["WETH/USDC"]["buy_dex"], "QuickSwap");
+
+    let scoped = call(addr, "get_prices", json!({ "market": "WETH/USDC" })).await;
+    assert_eq!(scoped["result"]["WETH/USDC"]["spread_usdc"], 5.0);
+
+    let missing = call(addr, "get_prices", json!({ "market": "nope" })).await;
+    assert_eq!(missing["ok"], false);
+}
+
+#[tokio::test]
+async fn unknown_method_returns_error() {
+    let db_file = tempfile::NamedTempFile::new().unwrap();
+    let db_path = db_file.path().to_str().unwrap().to_string();
+    db::init_db_at(&db_path).unwrap();
+
+    let addr = spawn_test_server(&db_path).await;
+
+    let response = call(addr, "not_a_real_method", json!({})).await;
+    assert_eq!(response["ok"], false);
+}